@@ -0,0 +1,75 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+
+/// Default location bootmgr looks for its declarative entry config.
+pub const DEFAULT_PATH: &str = "/etc/bootmgr.toml";
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub entries: Vec<EntryOverride>,
+    /// Prefer `kexec` into Linux kernel entries over a classic `BootNext`
+    /// firmware reboot. Overridden per-run by `--kexec`/`--no-kexec`.
+    #[serde(default)]
+    pub kexec: bool,
+    /// Auto-boot the pre-selected default entry after this many idle
+    /// seconds. `None` (or 0) disables the countdown.
+    #[serde(default)]
+    pub timeout_secs: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EntryOverride {
+    /// Matches an `Entry::id_string` exactly, e.g. "0001".
+    pub id: Option<String>,
+    /// Matches `Entry::description` as a regex when `id` isn't set.
+    pub description_regex: Option<String>,
+    /// Overrides the entry's displayed title.
+    pub title: Option<String>,
+    /// Forces the entry into or out of the Default menu, overriding the
+    /// `\efi\boot\boot*.efi` heuristic in `Entry::new`.
+    pub hidden: Option<bool>,
+    /// Higher weights sort first; ties keep firmware enumeration order.
+    #[serde(default)]
+    pub weight: i32,
+    /// Pre-selects this entry as the menu's initial choice.
+    #[serde(default)]
+    pub default: bool,
+}
+
+impl EntryOverride {
+    fn matches(&self, id_string: &str, description: &str) -> bool {
+        if let Some(id) = &self.id {
+            return id == id_string;
+        }
+
+        if let Some(pattern) = &self.description_regex {
+            return Regex::new(pattern)
+                .map(|re| re.is_match(description))
+                .unwrap_or(false);
+        }
+
+        false
+    }
+}
+
+impl Config {
+    /// Loads the config from `path`, returning an empty (no-op) config when
+    /// the file is absent so an uncurated system keeps working.
+    pub fn load(path: &str) -> Result<Config> {
+        if !Path::new(path).exists() {
+            return Ok(Config::default());
+        }
+
+        let raw = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+        toml::from_str(&raw).with_context(|| format!("Failed to parse {}", path))
+    }
+
+    pub fn find(&self, id_string: &str, description: &str) -> Option<&EntryOverride> {
+        self.entries.iter().find(|e| e.matches(id_string, description))
+    }
+}
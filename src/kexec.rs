@@ -0,0 +1,123 @@
+use std::ffi::CString;
+use std::fs::File;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use nix::errno::Errno;
+use nix::sys::reboot::{reboot, RebootMode};
+
+/// kexec_file_load(2) flag: don't expect/require a separate initrd.
+const KEXEC_FILE_NO_INITRAMFS: libc::c_ulong = 0x00000004;
+
+/// Where the ESP is mounted to resolve device-path file names into real
+/// paths. Distros that already mount it elsewhere (e.g. `/boot`) should
+/// bind-mount it here, same as they'd do for any other ESP consumer.
+const ESP_MOUNT_POINT: &str = "/boot/efi";
+
+/// Reasons `kexec_into` couldn't hand off to the kernel, so the caller can
+/// fall back to a classic `BootNext` reboot instead.
+#[derive(Debug)]
+pub enum KexecError {
+    /// kexec_file_load(2) returned ENOEXEC: the image isn't something the
+    /// running kernel's image verifier recognizes.
+    UnsupportedImage,
+    /// kexec_file_load(2) returned EPERM: kernel lockdown or secure boot
+    /// policy is blocking unsigned/unverified kexec.
+    Locked,
+    Other(anyhow::Error),
+}
+
+/// Heuristic for "this device-path file looks like a Linux kernel/EFI stub
+/// image", as opposed to a bootloader, shell, or other .efi payload.
+pub fn looks_like_linux_kernel(lowercase_path: &str) -> bool {
+    lowercase_path.contains("vmlinuz") ||
+        lowercase_path.contains(r"\efi\linux\") ||
+        lowercase_path.ends_with("linux.efi")
+}
+
+/// Resolves a `FilePathMediaDevicePath` string (as parsed in `Entry::new`)
+/// into a real filesystem path, mounting the ESP by its partition GUID
+/// first if it isn't mounted yet.
+pub fn resolve_esp_path(partition_guid: &str, device_path: &str) -> anyhow::Result<PathBuf> {
+    let mount_point = ensure_esp_mounted(partition_guid)?;
+    let relative = device_path.replace('\\', "/");
+    Ok(mount_point.join(relative.trim_start_matches('/')))
+}
+
+fn ensure_esp_mounted(partition_guid: &str) -> anyhow::Result<PathBuf> {
+    let mount_point = PathBuf::from(ESP_MOUNT_POINT);
+
+    if is_mounted(&mount_point)? {
+        return Ok(mount_point);
+    }
+
+    std::fs::create_dir_all(&mount_point)
+        .with_context(|| format!("Failed to create {}", mount_point.display()))?;
+
+    let source = format!("/dev/disk/by-partuuid/{}", partition_guid.to_lowercase());
+    nix::mount::mount(
+        Some(source.as_str()),
+        &mount_point,
+        Some("vfat"),
+        nix::mount::MsFlags::MS_RDONLY,
+        None::<&str>,
+    ).with_context(|| format!("Failed to mount ESP {} at {}", source, mount_point.display()))?;
+
+    Ok(mount_point)
+}
+
+fn is_mounted(path: &Path) -> anyhow::Result<bool> {
+    let mounts = std::fs::read_to_string("/proc/mounts").context("Failed to read /proc/mounts")?;
+    Ok(mounts.lines().any(|line| line.split_whitespace().nth(1) == path.to_str()))
+}
+
+/// Loads `kernel` (and optional `initrd`) via `kexec_file_load(2)` and
+/// reboots straight into it, skipping a full firmware reboot. On
+/// `KexecError::UnsupportedImage`/`::Locked` the caller should fall back to
+/// a plain `BootNext` reboot instead.
+pub fn kexec_into(kernel: &Path, initrd: Option<&Path>, cmdline: &str) -> Result<(), KexecError> {
+    let kernel_file = File::open(kernel)
+        .with_context(|| format!("Failed to open kernel {}", kernel.display()))
+        .map_err(KexecError::Other)?;
+
+    // Bound to the match's enclosing scope (not the arm) so the fd stays
+    // open until the syscall below runs.
+    let initrd_file: Option<File> = match initrd {
+        Some(path) => Some(
+            File::open(path)
+                .with_context(|| format!("Failed to open initrd {}", path.display()))
+                .map_err(KexecError::Other)?,
+        ),
+        None => None,
+    };
+    let (initrd_fd, flags): (i32, libc::c_ulong) = match &initrd_file {
+        Some(file) => (file.as_raw_fd(), 0),
+        None => (-1, KEXEC_FILE_NO_INITRAMFS),
+    };
+
+    let cmdline = CString::new(cmdline).map_err(|e| KexecError::Other(e.into()))?;
+    let cmdline_bytes = cmdline.as_bytes_with_nul();
+
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_kexec_file_load,
+            kernel_file.as_raw_fd(),
+            initrd_fd,
+            cmdline_bytes.len() as libc::c_ulong,
+            cmdline.as_ptr(),
+            flags,
+        )
+    };
+
+    if ret != 0 {
+        return Err(match Errno::last() {
+            Errno::ENOEXEC => KexecError::UnsupportedImage,
+            Errno::EPERM => KexecError::Locked,
+            errno => KexecError::Other(anyhow::anyhow!("kexec_file_load failed: {}", errno)),
+        });
+    }
+
+    reboot(RebootMode::RB_KEXEC).map_err(|e| KexecError::Other(e.into()))?;
+    Ok(())
+}
@@ -17,6 +17,7 @@ use crossterm::{
     }
 };
 
+use efivar::VarManager;
 use efivar::efi::{VariableName, VariableFlags};
 use uefi::proto::device_path::DevicePath;
 use regex::Regex;
@@ -28,6 +29,18 @@ use ez_input::RinputerHandle;
 use ez_input::EzEvent;
 use std::sync::mpsc::Sender;
 use std::sync::mpsc::channel;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
+
+mod config;
+use config::Config;
+
+mod kexec;
+
+// OsIndications bit asking the firmware to stop in its setup UI on the next
+// boot; OsIndications lives in the UEFI global variable namespace.
+const EFI_OS_INDICATIONS_BOOT_TO_FW_UI: u64 = 0x0000000000000001;
 
 fn char16_to_string(buf: &[u8]) -> (String, usize) {
     let mut iter = buf.iter();
@@ -61,6 +74,13 @@ struct Entry {
     description: String,
     path: Vec<String>,
     display_default: bool,
+    // Config-driven curation, applied after parsing; see `config::Config`.
+    weight: i32,
+    preselect: bool,
+    // Populated when the device path looks like a Linux kernel image on a
+    // GPT partition, so `main()` can try `kexec` instead of a full reboot.
+    kernel_path: Option<String>,
+    partition_guid: Option<String>,
 }
 
 impl Entry {
@@ -73,11 +93,19 @@ impl Entry {
 
         let mut display_default = false;
         let mut out_path: Vec<String> = Vec::new();
+        let mut kernel_path: Option<String> = None;
+        let mut partition_guid: Option<String> = None;
         for node in device_path.node_iter() {
-            if let Some(file) = node.as_file_path_media_device_path() {
+            if let Some(hd) = node.as_hard_drive_media_device_path() {
+                // Remember the ESP's partition GUID so a kexec boot can
+                // mount it later; signature type 2 is a GPT unique GUID.
+                if let Some(uefi::proto::device_path::media::PartitionSignature::Guid(guid)) = hd.partition_signature() {
+                    partition_guid = Some(guid.to_string());
+                }
+            } else if let Some(file) = node.as_file_path_media_device_path() {
                 let path = file.path_name().to_cstring16().unwrap();
                 let lowercase = path.to_string().to_lowercase();
-                
+
                 // ignore default selections
                 if lowercase.contains(r"\efi\boot\bootx64.efi") ||
                     lowercase.contains(r"\efi\boot\bootia.efi") ||
@@ -87,6 +115,10 @@ impl Entry {
                     display_default = true;
                 }
 
+                if kexec::looks_like_linux_kernel(&lowercase) {
+                    kernel_path = Some(lowercase.clone());
+                }
+
                 out_path.push(lowercase);
             } else {
                 out_path.push(format!("{:?}", node.device_type()));
@@ -106,8 +138,30 @@ impl Entry {
             description,
             path: out_path,
             display_default,
+            weight: 0,
+            preselect: false,
+            kernel_path,
+            partition_guid,
         }
     }
+
+    /// Applies a matching config override, if any: a custom title, a
+    /// forced hidden/shown state, a sort weight, and whether this entry
+    /// should be pre-selected when the menu opens.
+    fn apply_config(&mut self, cfg: &Config) {
+        let Some(ov) = cfg.find(&self.id_string, &self.description) else {
+            return;
+        };
+
+        if let Some(title) = &ov.title {
+            self.description = title.clone();
+        }
+        if let Some(hidden) = ov.hidden {
+            self.display_default = !hidden;
+        }
+        self.weight = ov.weight;
+        self.preselect = ov.default;
+    }
 }
 
 impl std::fmt::Display for Entry {
@@ -122,26 +176,71 @@ impl std::fmt::Display for Entry {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PowerAction {
+    Shutdown,
+    Reboot,
+    Suspend,
+    RebootToFirmwareSetup,
+}
+
+impl std::fmt::Display for PowerAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            PowerAction::Shutdown => "Shutdown",
+            PowerAction::Reboot => "Reboot",
+            PowerAction::Suspend => "Suspend",
+            PowerAction::RebootToFirmwareSetup => "Reboot to Firmware Setup",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 enum MenuChoice {
     Entry(Entry),
     Menu,
+    Power,
+    PowerAction(PowerAction),
 }
 
 #[derive(PartialEq)]
 enum MenuType {
     Default,
     Advanced,
+    Power,
+}
+
+// Normal mode types a search query; Command mode (entered with `:`, like a
+// modal editor) types a verb that's parsed and run against BootOrder/BootNext.
+#[derive(PartialEq)]
+enum MenuMode {
+    Normal,
+    Command,
+}
+
+// Gamepad/joystick events come in as `EzEvent` through ez_input, but the
+// keyboard thread also needs to feed through free text for the search box,
+// which has no equivalent on a pad.
+#[derive(Clone, Debug)]
+enum MenuEvent {
+    Pad(EzEvent),
+    Char(char),
+    Backspace,
+    Esc,
 }
 
-fn kbd_input(tx: Sender<EzEvent>) {
+fn kbd_input(tx: Sender<MenuEvent>) {
     loop {
         match event::read().unwrap() {
             Event::Key(key) => {
                 match key.code {
-                    KeyCode::Enter  => tx.send(EzEvent::South(true)).unwrap(),
-                    KeyCode::Down   => tx.send(EzEvent::DirectionDown).unwrap(),
-                    KeyCode::Up     => tx.send(EzEvent::DirectionUp).unwrap(),
+                    KeyCode::Enter     => tx.send(MenuEvent::Pad(EzEvent::South(true))).unwrap(),
+                    KeyCode::Down      => tx.send(MenuEvent::Pad(EzEvent::DirectionDown)).unwrap(),
+                    KeyCode::Up        => tx.send(MenuEvent::Pad(EzEvent::DirectionUp)).unwrap(),
+                    KeyCode::Esc       => tx.send(MenuEvent::Esc).unwrap(),
+                    KeyCode::Backspace => tx.send(MenuEvent::Backspace).unwrap(),
+                    KeyCode::Char(c)   => tx.send(MenuEvent::Char(c)).unwrap(),
                     _ => (),
                 }
             },
@@ -150,18 +249,211 @@ fn kbd_input(tx: Sender<EzEvent>) {
     }
 }
 
-fn pad_input(tx: Sender<EzEvent>) {
+fn pad_input(tx: Sender<MenuEvent>) {
     let mut handle = RinputerHandle::open().unwrap();
     loop {
         let ev = handle.get_event_blocking().unwrap();
-        tx.send(ev).unwrap();
+        tx.send(MenuEvent::Pad(ev)).unwrap();
+    }
+}
+
+// Renders an inline yes/no prompt over the current screen and blocks until
+// the user confirms or backs out, so a stray Enter can't power off the
+// machine by accident.
+fn confirm(out: &mut std::io::Stdout, rx: &Receiver<MenuEvent>, prompt: &str) -> Result<bool> {
+    loop {
+        out.queue(terminal::Clear(terminal::ClearType::All))?;
+        out.queue(cursor::MoveTo(1, 1))?;
+        out.queue(style::Print(format!("{}? [Enter: yes, Esc: no]", prompt)))?;
+        out.flush()?;
+
+        match rx.recv()? {
+            MenuEvent::Pad(EzEvent::South(true)) => return Ok(true),
+            MenuEvent::Esc => return Ok(false),
+            _ => {},
+        }
     }
 }
 
-fn menu(choices: &Vec<Entry>) -> Result<MenuChoice> {
+fn read_boot_order(manager: &mut dyn VarManager) -> Result<Vec<u16>> {
+    let var = VariableName::new("BootOrder");
+    let mut buf = [0u8; 1024];
+    let (_, len) = manager.read(&var, &mut buf)
+        .map_err(|e| anyhow::anyhow!("Failed to read BootOrder: {}", e))?;
+    Ok(buf[..len].chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect())
+}
+
+fn write_boot_order(manager: &mut dyn VarManager, order: &[u16]) -> Result<()> {
+    let var = VariableName::new("BootOrder");
+    let attr = VariableFlags::NON_VOLATILE | VariableFlags::BOOTSERVICE_ACCESS | VariableFlags::RUNTIME_ACCESS;
+    let bytes: Vec<u8> = order.iter().flat_map(|id| id.to_le_bytes()).collect();
+
+    manager.write(&var, attr, &bytes)
+        .map_err(|e| anyhow::anyhow!("Failed to write BootOrder: {}", e))?;
+    Ok(())
+}
+
+// Boots into `entry`: tries kexec first when `use_kexec` is set and the
+// entry looks like a kernel, falling back to a classic BootNext + firmware
+// reboot otherwise. Only returns on error; success reboots the machine.
+// Shared by `main`'s normal Enter-to-boot path and the `:boot` command so
+// neither one re-implements the kexec-vs-BootNext decision on its own.
+fn boot_entry(entry: &Entry, manager: &mut dyn VarManager, use_kexec: bool) -> Result<()> {
+    let kexec_result = if use_kexec {
+        entry.kernel_path.as_ref().zip(entry.partition_guid.as_ref())
+            .map(|(path, guid)| kexec::resolve_esp_path(guid, path))
+    } else {
+        None
+    };
+
+    let booted_via_kexec = match kexec_result {
+        Some(Ok(kernel_path)) => {
+            // no separate initrd/cmdline: distro kernels on the ESP are
+            // unified EFI stub images that embed both
+            match kexec::kexec_into(&kernel_path, None, "") {
+                Ok(()) => true,
+                Err(kexec::KexecError::UnsupportedImage) | Err(kexec::KexecError::Locked) => {
+                    eprintln!("kexec unavailable for this entry, falling back to BootNext");
+                    false
+                },
+                Err(kexec::KexecError::Other(e)) => return Err(e),
+            }
+        },
+        Some(Err(e)) => {
+            eprintln!("Failed to resolve kernel path for kexec, falling back to BootNext: {}", e);
+            false
+        },
+        None => false,
+    };
+
+    if !booted_via_kexec {
+        let next = VariableName::new("BootNext");
+        let attr = VariableFlags::NON_VOLATILE | VariableFlags::BOOTSERVICE_ACCESS | VariableFlags::RUNTIME_ACCESS;
+        let val: [u8; 2] = entry.id.to_le_bytes();
+
+        manager.write(&next, attr, &val)
+            .map_err(|e| anyhow::anyhow!("Failed to write BootNext: {}", e))?;
+
+        reboot(RebootMode::RB_AUTOBOOT)?;
+    }
+
+    Ok(())
+}
+
+// Verbs that write real BootOrder/BootNext EFI variables or reboot the
+// machine; these must respect the same `actually-boot` dry-run gate as
+// every other destructive action in `main()`. `up`/`down` only reorder
+// the in-memory `boot_order` (nothing is live until `write`), so they're
+// left out and always run.
+const DESTRUCTIVE_VERBS: &[&str] = &["next", "boot", "default", "write"];
+
+// Parses and runs one command-mode verb against the selected entry:
+//   next              - one-shot BootNext, stay in the menu
+//   boot              - kexec (if enabled) or BootNext, then reboot immediately
+//   default           - move the entry to the front of BootOrder and write it
+//   up / down         - reorder the entry within the in-memory BootOrder
+//   write             - persist the in-memory BootOrder
+// Returns the status line to render, or an error describing what failed.
+fn run_command(cmd: &str, manager: &mut dyn VarManager, boot_order: &mut Vec<u16>, selected: Option<&Entry>, use_kexec: bool, actually_boot: bool) -> Result<String> {
+    let mut words = cmd.split_whitespace();
+    let verb = words.next().unwrap_or("");
+
+    if !actually_boot && DESTRUCTIVE_VERBS.contains(&verb) {
+        return Ok(format!("dry run: pass actually-boot as the 1st argument to run '{}'", verb));
+    }
+
+    let entry = selected.context("No entry selected")?;
+
+    match verb {
+        "next" => {
+            let attr = VariableFlags::NON_VOLATILE | VariableFlags::BOOTSERVICE_ACCESS | VariableFlags::RUNTIME_ACCESS;
+            manager.write(&VariableName::new("BootNext"), attr, &entry.id.to_le_bytes())
+                .map_err(|e| anyhow::anyhow!("Failed to write BootNext: {}", e))?;
+            Ok(format!("BootNext set to {:04X}", entry.id))
+        },
+        "boot" => {
+            boot_entry(entry, manager, use_kexec)?;
+            Ok(String::new()) // unreachable: boot_entry() doesn't return on success
+        },
+        "default" => {
+            boot_order.retain(|id| *id != entry.id);
+            boot_order.insert(0, entry.id);
+            write_boot_order(manager, boot_order)?;
+            Ok(format!("{:04X} set as default and written to BootOrder", entry.id))
+        },
+        "up" | "down" => {
+            let pos = boot_order.iter().position(|id| *id == entry.id)
+                .context("Entry not present in BootOrder")?;
+            let new_pos = if verb == "up" { pos.checked_sub(1) } else { Some(pos + 1) };
+            match new_pos {
+                Some(new_pos) if new_pos < boot_order.len() => {
+                    boot_order.swap(pos, new_pos);
+                    Ok(format!("Moved {:04X} to position {}", entry.id, new_pos))
+                },
+                _ => Ok(format!("{:04X} is already at the {} of BootOrder", entry.id, if verb == "up" { "top" } else { "bottom" })),
+            }
+        },
+        "write" => {
+            write_boot_order(manager, boot_order)?;
+            Ok("BootOrder written".to_string())
+        },
+        "" => Ok(String::new()),
+        other => Err(anyhow::anyhow!("Unknown command '{}'", other)),
+    }
+}
+
+// Case-insensitive substring match against each entry's description and
+// device path; non-entry items (submenu markers) always pass through so
+// the menu structure doesn't disappear while searching.
+fn filter_menu(items: &[MenuChoice], query: &str) -> Vec<MenuChoice> {
+    if query.is_empty() {
+        return items.to_vec();
+    }
+
+    let query = query.to_lowercase();
+    items.iter().filter(|c| match c {
+        MenuChoice::Entry(entry) => {
+            entry.description.to_lowercase().contains(&query) ||
+                entry.path.iter().any(|p| p.to_lowercase().contains(&query))
+        },
+        _ => true,
+    }).cloned().collect()
+}
+
+// Prints `text`, highlighting the first substring matching `query` (if any)
+// in a different color so a live search visibly shows its match.
+fn queue_highlighted(out: &mut std::io::Stdout, text: &str, query: &str) -> Result<()> {
+    // `lower`'s byte offsets are only valid to slice back into `text` when
+    // lowercasing can't change a character's UTF-8 length (true for ASCII,
+    // not guaranteed in general: e.g. the 3-byte Kelvin sign U+212A
+    // lowercases to the 1-byte ASCII 'k').
+    // Some firmware vendors embed non-ASCII strings, so fall back to plain
+    // (unhighlighted) printing rather than slice on the wrong boundary.
+    if query.is_empty() || !text.is_ascii() {
+        out.queue(style::Print(text.to_string()))?;
+        return Ok(());
+    }
+
+    let lower = text.to_lowercase();
+    let query = query.to_lowercase();
+    if let Some(start) = lower.find(&query) {
+        let end = start + query.len();
+        out.queue(style::Print(text[..start].to_string()))?;
+        out.queue(style::SetForegroundColor(style::Color::Yellow))?;
+        out.queue(style::Print(text[start..end].to_string()))?;
+        out.queue(style::ResetColor)?;
+        out.queue(style::Print(text[end..].to_string()))?;
+    } else {
+        out.queue(style::Print(text.to_string()))?;
+    }
+
+    Ok(())
+}
+
+fn menu(choices: &Vec<Entry>, manager: &mut dyn VarManager, timeout_secs: Option<u32>, use_kexec: bool, actually_boot: bool) -> Result<MenuChoice> {
     let mut out = stdout();
 
-    let (tx1, rx) = channel::<EzEvent>();
+    let (tx1, rx) = channel::<MenuEvent>();
     let tx2 = tx1.clone();
     std::thread::spawn(move || kbd_input(tx1));
     std::thread::spawn(move || pad_input(tx2));
@@ -173,12 +465,30 @@ fn menu(choices: &Vec<Entry>) -> Result<MenuChoice> {
 
     let mut chosen = false;
     let mut pos: usize = 0;
+    let mut query = String::new();
+    let mut mode = MenuMode::Normal;
+    let mut command_buf = String::new();
+    let mut command_status: Option<String> = None;
+    let mut boot_order = read_boot_order(manager).unwrap_or_else(|_| choices.iter().map(|e| e.id).collect());
+    // Some(n): n idle seconds left before auto-boot; None: disabled, or
+    // cancelled for the rest of this session by a keypress.
+    let mut remaining = timeout_secs.filter(|&s| s > 0);
     let mut menu_items: Vec<MenuChoice> = Vec::new();
     let mut all_items: Vec<MenuChoice> = Vec::new();
+    let power_items: Vec<MenuChoice> = vec![
+        MenuChoice::PowerAction(PowerAction::Shutdown),
+        MenuChoice::PowerAction(PowerAction::Reboot),
+        MenuChoice::PowerAction(PowerAction::Suspend),
+        MenuChoice::PowerAction(PowerAction::RebootToFirmwareSetup),
+    ];
     let mut cur_choice: Option<MenuChoice> = None;
     let mut what_to_display = MenuType::Default;
 
     for entry in choices.iter() {
+        if entry.preselect {
+            cur_choice = Some(MenuChoice::Entry(entry.clone()));
+        }
+
         if entry.display_default {
             if cur_choice.is_none() {
                 cur_choice = Some(MenuChoice::Entry(entry.clone()));
@@ -190,15 +500,50 @@ fn menu(choices: &Vec<Entry>) -> Result<MenuChoice> {
     }
 
     menu_items.push(MenuChoice::Menu);
+    menu_items.push(MenuChoice::Power);
+
+    // The entry idle-timeout auto-boot picks (the config-preselected entry,
+    // or else the first default-visible one), captured once and kept
+    // as-is even when it's `hidden: true` and so never
+    // appears in `cur_menu`. Unlike `cur_choice` below, this never falls
+    // back to `cur_menu.first()` — a deliberately hidden default should
+    // still be what the countdown boots into, not an arbitrary visible
+    // entry that happens to be first.
+    let preselect_choice = cur_choice.clone();
 
     while !chosen {
-        let cur_menu = match what_to_display {
+        let base_menu = match what_to_display {
             MenuType::Default => &menu_items,
             MenuType::Advanced => &all_items,
+            MenuType::Power => &power_items,
         };
+        let cur_menu = filter_menu(base_menu, &query);
+
+        // `pos` (what's highlighted) must always track `cur_choice` (what
+        // Enter submits), even when a config preselect or search landed on
+        // an entry that isn't first in `cur_menu` — or isn't in it at all.
+        match cur_menu.iter().position(|c| Some(c) == cur_choice.as_ref()) {
+            Some(idx) => pos = idx,
+            None => {
+                pos = 0;
+                cur_choice = cur_menu.first().cloned();
+            },
+        }
+
         out.queue(terminal::Clear(terminal::ClearType::All))?;
         out.queue(cursor::MoveTo(1, 1))?;
         out.queue(style::Print("Choose boot selection"))?;
+        if let Some(secs) = remaining {
+            let default_desc = match &preselect_choice {
+                Some(MenuChoice::Entry(entry)) => entry.description.as_str(),
+                _ => "default",
+            };
+            out.queue(cursor::MoveTo(1, 2))?;
+            out.queue(style::Print(format!("Booting {} in {}s", default_desc, secs)))?;
+        } else if !query.is_empty() {
+            out.queue(cursor::MoveTo(1, 2))?;
+            out.queue(style::Print(format!("search: {}", query)))?;
+        }
 
         // print out default-visible options + boot menu
         out.queue(cursor::MoveTo(4, 3))?;
@@ -207,41 +552,110 @@ fn menu(choices: &Vec<Entry>) -> Result<MenuChoice> {
             match c {
                 MenuChoice::Entry(entry) => {
                     if what_to_display == MenuType::Default {
-                        out.queue(style::Print(&entry.description))?;
+                        queue_highlighted(&mut out, &entry.description, &query)?;
                     } else {
-                        out.queue(style::Print(entry.to_string()))?;
+                        queue_highlighted(&mut out, &entry.to_string(), &query)?;
                     }
-                    out.queue(cursor::MoveToNextLine(1))?;
-                    out.queue(cursor::MoveToColumn(4))?;
                 },
                 MenuChoice::Menu => {
                     out.queue(style::Print("Advanced Boot Menu"))?;
-                }
+                },
+                MenuChoice::Power => {
+                    out.queue(style::Print("Power Off / Reboot"))?;
+                },
+                MenuChoice::PowerAction(action) => {
+                    out.queue(style::Print(action.to_string()))?;
+                },
             }
+            out.queue(cursor::MoveToNextLine(1))?;
+            out.queue(cursor::MoveToColumn(4))?;
         }
 
         out.queue(cursor::MoveTo(1, (3+pos) as u16))?;
         out.queue(style::Print("=>"))?;
+
+        let (_, rows) = terminal::size()?;
+        out.queue(cursor::MoveTo(1, rows.saturating_sub(1)))?;
+        if mode == MenuMode::Command {
+            out.queue(style::Print(format!(":{}", command_buf)))?;
+        } else if let Some(status) = &command_status {
+            out.queue(style::Print(status))?;
+        }
+
         out.flush()?;
 
-        match rx.recv()? {
-            EzEvent::DirectionDown => {
+        let event = match rx.recv_timeout(Duration::from_secs(1)) {
+            Ok(event) => {
+                // any real input cancels the auto-boot countdown for the session
+                remaining = None;
+                event
+            },
+            Err(RecvTimeoutError::Timeout) => {
+                if let Some(secs) = remaining {
+                    if preselect_choice.is_none() {
+                        // Nothing to auto-boot into (no preselected entry
+                        // and no default-visible entry): disable the
+                        // countdown instead of firing on a None choice.
+                        remaining = None;
+                    } else if secs <= 1 {
+                        // Boot the configured preselect, not whatever
+                        // `cur_choice` fell back to for highlighting.
+                        cur_choice = preselect_choice.clone();
+                        chosen = true;
+                    } else {
+                        remaining = Some(secs - 1);
+                    }
+                }
+                continue;
+            },
+            Err(RecvTimeoutError::Disconnected) => return Err(anyhow::anyhow!("input channel disconnected")),
+        };
+
+        match event {
+            MenuEvent::Pad(EzEvent::DirectionDown) => {
                 if let Some(new) = cur_menu.get(pos+1) {
                     pos += 1;
                     cur_choice = Some(new.clone());
                 }
             },
-            EzEvent::South(val) => {
+            MenuEvent::Pad(EzEvent::South(val)) => {
                 println!("val {}", val);
                 if val == true {
-                    if cur_choice != Some(MenuChoice::Menu) {
-                        chosen = true;
+                    if mode == MenuMode::Command {
+                        let selected = match &cur_choice {
+                            Some(MenuChoice::Entry(entry)) => Some(entry),
+                            _ => None,
+                        };
+                        command_status = Some(match run_command(&command_buf, manager, &mut boot_order, selected, use_kexec, actually_boot) {
+                            Ok(msg) => msg,
+                            Err(e) => format!("error: {}", e),
+                        });
+                        mode = MenuMode::Normal;
+                        command_buf.clear();
                     } else {
-                        what_to_display = MenuType::Advanced;
+                        match cur_choice {
+                            Some(MenuChoice::Menu) => {
+                                what_to_display = MenuType::Advanced;
+                                query.clear();
+                                pos = 0;
+                            },
+                            Some(MenuChoice::Power) => {
+                                what_to_display = MenuType::Power;
+                                query.clear();
+                                pos = 0;
+                            },
+                            Some(MenuChoice::PowerAction(action)) => {
+                                if confirm(&mut out, &rx, &format!("Really {}", action))? {
+                                    chosen = true;
+                                }
+                            },
+                            Some(MenuChoice::Entry(_)) => chosen = true,
+                            None => {},
+                        }
                     }
                 }
             },
-            EzEvent::DirectionUp => {
+            MenuEvent::Pad(EzEvent::DirectionUp) => {
                 // avoid overflow panics
                 if pos > 0 {
                     if let Some(new) = cur_menu.get(pos-1) {
@@ -250,7 +664,44 @@ fn menu(choices: &Vec<Entry>) -> Result<MenuChoice> {
                     }
                 }
             },
-            _ => {},
+            MenuEvent::Char(c) if mode == MenuMode::Command => {
+                command_buf.push(c);
+            },
+            MenuEvent::Char(':') if mode == MenuMode::Normal => {
+                mode = MenuMode::Command;
+                command_buf.clear();
+                command_status = None;
+            },
+            MenuEvent::Char(c) if c.to_digit(10).is_some_and(|d| (1..=9).contains(&d)) => {
+                let idx = c.to_digit(10).unwrap() as usize - 1;
+                if let Some(new) = cur_menu.get(idx) {
+                    pos = idx;
+                    cur_choice = Some(new.clone());
+                }
+            },
+            MenuEvent::Char(c) => {
+                query.push(c);
+                pos = 0;
+                cur_choice = filter_menu(base_menu, &query).into_iter().next();
+            },
+            MenuEvent::Backspace if mode == MenuMode::Command => {
+                command_buf.pop();
+            },
+            MenuEvent::Backspace => {
+                query.pop();
+                pos = 0;
+                cur_choice = filter_menu(base_menu, &query).into_iter().next();
+            },
+            MenuEvent::Esc if mode == MenuMode::Command => {
+                mode = MenuMode::Normal;
+                command_buf.clear();
+            },
+            MenuEvent::Esc => {
+                query.clear();
+                pos = 0;
+                cur_choice = filter_menu(base_menu, &query).into_iter().next();
+            },
+            MenuEvent::Pad(_) => {},
         }
     }
 
@@ -266,12 +717,14 @@ fn main() -> Result<()> {
     let mut manager = efivar::system();
     let mut buf: [u8; 1024] = [0u8; 1024];
     let mut options: Vec<Entry> = Vec::new();
+    let cfg = Config::load(config::DEFAULT_PATH)?;
 
     for var in manager.get_var_names().expect("Failed to get efivar names") {
         if boot_xxxx.is_match(var.variable()) {
             match manager.read(&var, &mut buf)  {
                 Ok(..) => {
-                    let tmp = Entry::new(var.variable(), &buf);
+                    let mut tmp = Entry::new(var.variable(), &buf);
+                    tmp.apply_config(&cfg);
                     options.push(tmp);
                 }
                 Err(e) => eprintln!("{}", e),
@@ -279,18 +732,48 @@ fn main() -> Result<()> {
         }
     }
 
-    let choice = menu(&options).context("Wrong selection chosen")?;
-    if std::env::args().nth(1).unwrap_or("asdfasdf".to_string()) == "actually-boot" {
-        if let MenuChoice::Entry(e) = choice {
-            let next = VariableName::new("BootNext");
-            let attr = VariableFlags::NON_VOLATILE | VariableFlags::BOOTSERVICE_ACCESS | VariableFlags::RUNTIME_ACCESS;
-            let val: [u8; 2] = e.id.to_le_bytes();
+    // higher weight first; ties keep firmware enumeration order
+    options.sort_by(|a, b| b.weight.cmp(&a.weight));
+
+    let args: Vec<String> = std::env::args().collect();
+    let mut use_kexec = cfg.kexec;
+    if args.iter().any(|a| a == "--kexec") {
+        use_kexec = true;
+    }
+    if args.iter().any(|a| a == "--no-kexec") {
+        use_kexec = false;
+    }
 
-            manager.write(&next, attr, &val).expect("Failed to write BootNext");
+    let actually_boot = args.get(1).map(|s| s.as_str()).unwrap_or("asdfasdf") == "actually-boot";
 
-            reboot(RebootMode::RB_AUTOBOOT)?; // TODO: kexec into linux kernels
-        } else {
-            unreachable!();
+    let choice = menu(&options, &mut *manager, cfg.timeout_secs, use_kexec, actually_boot).context("Wrong selection chosen")?;
+    if actually_boot {
+        match choice {
+            MenuChoice::Entry(e) => boot_entry(&e, &mut *manager, use_kexec)?,
+            MenuChoice::PowerAction(PowerAction::Shutdown) => {
+                reboot(RebootMode::RB_POWER_OFF)?;
+            },
+            MenuChoice::PowerAction(PowerAction::Reboot) => {
+                reboot(RebootMode::RB_AUTOBOOT)?;
+            },
+            MenuChoice::PowerAction(PowerAction::Suspend) => {
+                std::fs::write("/sys/power/state", "mem").context("Failed to suspend")?;
+            },
+            MenuChoice::PowerAction(PowerAction::RebootToFirmwareSetup) => {
+                let var = VariableName::new("OsIndications");
+                let attr = VariableFlags::NON_VOLATILE | VariableFlags::BOOTSERVICE_ACCESS | VariableFlags::RUNTIME_ACCESS;
+                let mut buf = [0u8; 8];
+                let cur = match manager.read(&var, &mut buf) {
+                    Ok(..) => u64::from_le_bytes(buf),
+                    Err(..) => 0,
+                };
+                let val = (cur | EFI_OS_INDICATIONS_BOOT_TO_FW_UI).to_le_bytes();
+
+                manager.write(&var, attr, &val).expect("Failed to write OsIndications");
+
+                reboot(RebootMode::RB_AUTOBOOT)?;
+            },
+            _ => unreachable!(),
         }
     } else {
         println!("{:#?}", choice);